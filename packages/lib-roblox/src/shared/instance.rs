@@ -1,13 +1,97 @@
-use std::borrow::{Borrow, Cow};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use rbx_dom_weak::types::{Variant as DomValue, VariantType as DomType};
-use rbx_reflection::{ClassTag, DataType};
+use rbx_reflection::{
+    ClassTag, DataType, PropertyDescriptor, PropertyTag, ReflectionDatabase, Scriptability,
+};
+
+thread_local! {
+    static REFLECTION_DATABASE_OVERRIDE: RefCell<Option<ReflectionDatabase<'static>>> =
+        const { RefCell::new(None) };
+}
+
+/**
+    Overrides the reflection database used for all reflection lookups
+    made on the current thread, such as custom classes or a newer
+    Roblox release not yet covered by the bundled database.
+
+    Passing `None` clears the override and restores the default
+    behavior of using the reflection database bundled with Lune.
+*/
+pub fn set_reflection_database(database: Option<ReflectionDatabase<'static>>) {
+    REFLECTION_DATABASE_OVERRIDE.with(|current| {
+        *current.borrow_mut() = database;
+    });
+}
+
+/**
+    Runs the given function with the reflection database currently in
+    use on this thread, falling back to the database bundled with
+    Lune if no override has been set using [`set_reflection_database`].
+*/
+fn with_reflection_database<F, R>(f: F) -> R
+where
+    F: FnOnce(&ReflectionDatabase) -> R,
+{
+    REFLECTION_DATABASE_OVERRIDE.with(|current| match &*current.borrow() {
+        Some(database) => f(database),
+        None => f(rbx_reflection_database::get()),
+    })
+}
 
 pub(crate) struct PropertyInfo {
     pub enum_name: Option<Cow<'static, str>>,
     pub enum_default: Option<u32>,
     pub value_type: Option<DomType>,
-    pub value_default: Option<&'static DomValue>,
+    pub value_default: Option<DomValue>,
+    pub scriptability: Scriptability,
+    pub tags: HashSet<PropertyTag>,
+}
+
+/**
+    Builds a [`PropertyInfo`] from a property descriptor and its
+    default value, if the class that owns it declares one.
+*/
+fn property_info_from_descriptor(
+    prop_definition: &PropertyDescriptor,
+    prop_default: Option<&DomValue>,
+) -> PropertyInfo {
+    let scriptability = prop_definition.scriptability.clone();
+    // Collected explicitly rather than cloned directly, since upstream may
+    // expose `tags` as a different set type than the one we store it as
+    let tags: HashSet<PropertyTag> = prop_definition.tags.iter().cloned().collect();
+
+    match &prop_definition.data_type {
+        DataType::Enum(enum_name) => PropertyInfo {
+            enum_name: Some(Cow::Owned(enum_name.to_string())),
+            enum_default: prop_default.and_then(|default| match default {
+                DomValue::Enum(enum_default) => Some(enum_default.to_u32()),
+                _ => None,
+            }),
+            value_type: None,
+            value_default: None,
+            scriptability,
+            tags,
+        },
+        DataType::Value(value_type) => PropertyInfo {
+            enum_name: None,
+            enum_default: None,
+            value_type: Some(*value_type),
+            value_default: prop_default.cloned(),
+            scriptability,
+            tags,
+        },
+        _ => PropertyInfo {
+            enum_name: None,
+            enum_default: None,
+            value_type: None,
+            value_default: None,
+            scriptability,
+            tags,
+        },
+    }
 }
 
 /**
@@ -22,57 +106,206 @@ pub(crate) fn find_property_info(
     instance_class: impl AsRef<str>,
     property_name: impl AsRef<str>,
 ) -> Option<PropertyInfo> {
-    let db = rbx_reflection_database::get();
-
     let instance_class = instance_class.as_ref();
     let property_name = property_name.as_ref();
 
-    let mut current_class = Cow::Borrowed(instance_class);
-    while let Some(class) = db.classes.get(current_class.as_ref()) {
-        if let Some(prop_definition) = class.properties.get(property_name) {
-            // We found a property, we should map it to a property
-            // info containing name/type and default property value
-            let prop_default = class.default_properties.get(property_name);
-            return Some(match &prop_definition.data_type {
-                DataType::Enum(enum_name) => PropertyInfo {
-                    enum_name: Some(Cow::Borrowed(enum_name)),
-                    enum_default: prop_default.and_then(|default| match default {
-                        DomValue::Enum(enum_default) => Some(enum_default.to_u32()),
-                        _ => None,
-                    }),
-                    value_type: None,
-                    value_default: None,
-                },
-                DataType::Value(value_type) => PropertyInfo {
-                    enum_name: None,
-                    enum_default: None,
-                    value_type: Some(*value_type),
-                    value_default: prop_default,
-                },
-                _ => PropertyInfo {
-                    enum_name: None,
-                    enum_default: None,
-                    value_type: None,
-                    value_default: None,
-                },
-            });
-        } else if let Some(sup) = &class.superclass {
-            // No property found, we should look at the superclass
-            current_class = Cow::Borrowed(sup)
-        } else {
-            break;
+    with_reflection_database(|db| {
+        let mut current_class = Cow::Borrowed(instance_class);
+        while let Some(class) = db.classes.get(current_class.as_ref()) {
+            if let Some(prop_definition) = class.properties.get(property_name) {
+                // We found a property, we should map it to a property
+                // info containing name/type and default property value
+                let prop_default = class.default_properties.get(property_name);
+                return Some(property_info_from_descriptor(prop_definition, prop_default));
+            } else if let Some(sup) = &class.superclass {
+                // No property found, we should look at the superclass
+                current_class = Cow::Owned(sup.to_string())
+            } else {
+                break;
+            }
         }
-    }
 
-    None
+        None
+    })
+}
+
+/**
+    Collects the info of every property of the given class, including
+    those inherited from its superclasses.
+
+    If a subclass and one of its superclasses both declare a property
+    with the same name, the subclass's property takes precedence.
+
+    Returns `None` if the class does not exist.
+*/
+pub(crate) fn collect_property_infos(
+    instance_class: impl AsRef<str>,
+) -> Option<Vec<(Cow<'static, str>, PropertyInfo)>> {
+    let instance_class = instance_class.as_ref();
+
+    with_reflection_database(|db| {
+        db.classes.get(instance_class)?;
+
+        let mut seen = BTreeSet::new();
+        let mut infos = Vec::new();
+
+        let mut current_class = Cow::Borrowed(instance_class);
+        while let Some(class) = db.classes.get(current_class.as_ref()) {
+            for (prop_name, prop_definition) in &class.properties {
+                if seen.insert(prop_name.to_string()) {
+                    let prop_default = class.default_properties.get(prop_name.as_ref());
+                    let info = property_info_from_descriptor(prop_definition, prop_default);
+                    infos.push((Cow::Owned(prop_name.to_string()), info));
+                }
+            }
+
+            match &class.superclass {
+                Some(sup) => current_class = Cow::Owned(sup.to_string()),
+                None => break,
+            }
+        }
+
+        Some(infos)
+    })
+}
+
+/**
+    Builds the full set of default property values for the given class,
+    including defaults inherited from its superclasses.
+
+    If a subclass and one of its superclasses both declare a default for
+    the same property, the subclass's default takes precedence.
+
+    This produces the complete default state that a freshly created
+    instance of the class should have.
+
+    Returns `None` if the class does not exist.
+*/
+pub(crate) fn default_properties_for(
+    instance_class: impl AsRef<str>,
+) -> Option<HashMap<String, DomValue>> {
+    let instance_class = instance_class.as_ref();
+
+    with_reflection_database(|db| {
+        db.classes.get(instance_class)?;
+
+        let mut chain = Vec::new();
+
+        let mut current_class = Cow::Borrowed(instance_class);
+        while let Some(class) = db.classes.get(current_class.as_ref()) {
+            chain.push(class);
+            match &class.superclass {
+                Some(sup) => current_class = Cow::Owned(sup.to_string()),
+                None => break,
+            }
+        }
+
+        let mut defaults = HashMap::new();
+        // Walk from the topmost superclass down to the requested class,
+        // so that a subclass's defaults override its ancestors'
+        for class in chain.into_iter().rev() {
+            for (prop_name, prop_value) in &class.default_properties {
+                defaults.insert(prop_name.to_string(), prop_value.clone());
+            }
+        }
+
+        Some(defaults)
+    })
 }
 
 /**
     Checks if an instance class exists in the reflection database.
 */
 pub fn class_exists(class_name: impl AsRef<str>) -> bool {
-    let db = rbx_reflection_database::get();
-    db.classes.contains_key(class_name.as_ref())
+    with_reflection_database(|db| db.classes.contains_key(class_name.as_ref()))
+}
+
+/**
+    Checks if an enum exists in the reflection database.
+*/
+pub fn enum_exists(enum_name: impl AsRef<str>) -> bool {
+    with_reflection_database(|db| db.enums.contains_key(enum_name.as_ref()))
+}
+
+/**
+    The info of an item belonging to an enum in the reflection database.
+
+    Note that `aliased` only reflects whether another item of the same
+    enum shares this item's numeric value - the reflection database does
+    not carry per-item deprecation data, so this is not a true legacy or
+    deprecated marker.
+*/
+pub(crate) struct EnumItemInfo {
+    pub name: Cow<'static, str>,
+    pub value: u32,
+    pub aliased: bool,
+}
+
+/**
+    A query used to look up a specific item of an enum,
+    either by its name or by its underlying numeric value.
+*/
+pub(crate) enum EnumItemQuery<'a> {
+    Name(&'a str),
+    Value(u32),
+}
+
+impl<'a> From<&'a str> for EnumItemQuery<'a> {
+    fn from(name: &'a str) -> Self {
+        EnumItemQuery::Name(name)
+    }
+}
+
+impl From<u32> for EnumItemQuery<'_> {
+    fn from(value: u32) -> Self {
+        EnumItemQuery::Value(value)
+    }
+}
+
+/**
+    Finds the info of an item belonging to an enum, looked up by
+    either its name or its underlying numeric value.
+
+    The returned name is always the canonical one for the resolved
+    value: when multiple item names share the same numeric value, the
+    lexicographically smallest name is treated as canonical, so lookups
+    by value are deterministic.
+
+    Returns `None` if the enum or the enum item does not exist.
+*/
+pub(crate) fn find_enum_item<'a>(
+    enum_name: impl AsRef<str>,
+    item: impl Into<EnumItemQuery<'a>>,
+) -> Option<EnumItemInfo> {
+    with_reflection_database(|db| {
+        let enum_descriptor = db.enums.get(enum_name.as_ref())?;
+
+        let item_value = match item.into() {
+            EnumItemQuery::Name(name) => *enum_descriptor.items.get(name)?,
+            EnumItemQuery::Value(value) => {
+                enum_descriptor.items.values().find(|v| **v == value)?;
+                value
+            }
+        };
+
+        let mut sharing_value_count = 0;
+        let canonical_name = enum_descriptor
+            .items
+            .iter()
+            .filter(|(_, other_value)| **other_value == item_value)
+            .inspect(|_| sharing_value_count += 1)
+            .map(|(name, _)| name)
+            .min()
+            .expect("item_value was just resolved from this same items map")
+            .to_string();
+        let aliased = sharing_value_count > 1;
+
+        Some(EnumItemInfo {
+            name: Cow::Owned(canonical_name),
+            value: item_value,
+            aliased,
+        })
+    })
 }
 
 /**
@@ -84,24 +317,25 @@ pub fn class_exists(class_name: impl AsRef<str>) -> bool {
     that does not exist in the currently known class reflection database.
 */
 pub fn class_is_a(instance_class: impl AsRef<str>, class_name: impl AsRef<str>) -> Option<bool> {
-    let mut instance_class = instance_class.as_ref();
+    let instance_class = instance_class.as_ref();
     let class_name = class_name.as_ref();
 
     if class_name == "Instance" || instance_class == class_name {
         Some(true)
     } else {
-        let db = rbx_reflection_database::get();
-
-        while instance_class != class_name {
-            let class_descriptor = db.classes.get(instance_class)?;
-            if let Some(sup) = &class_descriptor.superclass {
-                instance_class = sup.borrow();
-            } else {
-                return Some(false);
+        with_reflection_database(|db| {
+            let mut current_class = Cow::Borrowed(instance_class);
+            while current_class.as_ref() != class_name {
+                let class_descriptor = db.classes.get(current_class.as_ref())?;
+                if let Some(sup) = &class_descriptor.superclass {
+                    current_class = Cow::Owned(sup.to_string());
+                } else {
+                    return Some(false);
+                }
             }
-        }
 
-        Some(true)
+            Some(true)
+        })
     }
 }
 
@@ -115,22 +349,23 @@ pub fn class_is_a(instance_class: impl AsRef<str>, class_name: impl AsRef<str>)
     that does not exist in the currently known class reflection database.
 */
 pub fn class_is_a_service(instance_class: impl AsRef<str>) -> Option<bool> {
-    let mut instance_class = instance_class.as_ref();
-
-    let db = rbx_reflection_database::get();
-
-    loop {
-        let class_descriptor = db.classes.get(instance_class)?;
-        if class_descriptor.tags.contains(&ClassTag::Service) {
-            return Some(true);
-        } else if let Some(sup) = &class_descriptor.superclass {
-            instance_class = sup.borrow();
-        } else {
-            break;
+    let instance_class = instance_class.as_ref();
+
+    with_reflection_database(|db| {
+        let mut current_class = Cow::Borrowed(instance_class);
+        loop {
+            let class_descriptor = db.classes.get(current_class.as_ref())?;
+            if class_descriptor.tags.contains(&ClassTag::Service) {
+                return Some(true);
+            } else if let Some(sup) = &class_descriptor.superclass {
+                current_class = Cow::Owned(sup.to_string());
+            } else {
+                break;
+            }
         }
-    }
 
-    Some(false)
+        Some(false)
+    })
 }
 
 #[cfg(test)]
@@ -176,4 +411,93 @@ mod tests {
         assert_eq!(class_is_a_service("Work-space"), None);
         assert_eq!(class_is_a_service("CSG Dictionary Service"), None);
     }
+
+    #[test]
+    fn reflection_database_override() {
+        assert!(class_exists("Part"));
+        assert!(!class_exists("TotallyNotARealClass"));
+
+        let mut custom = rbx_reflection_database::get().clone();
+        custom.classes.remove("Part");
+
+        set_reflection_database(Some(custom));
+        assert!(!class_exists("Part"));
+
+        set_reflection_database(None);
+        assert!(class_exists("Part"));
+    }
+
+    #[test]
+    fn enum_exists_valid() {
+        assert!(enum_exists("Material"));
+        assert!(enum_exists("KeyCode"));
+    }
+
+    #[test]
+    fn enum_exists_invalid() {
+        assert!(!enum_exists("NotAnEnum"));
+        assert!(!enum_exists(""));
+    }
+
+    #[test]
+    fn find_enum_item_by_name() {
+        let info = find_enum_item("Material", "Plastic").unwrap();
+        assert_eq!(info.name, "Plastic");
+
+        assert!(find_enum_item("Material", "NotAMaterial").is_none());
+        assert!(find_enum_item("NotAnEnum", "Plastic").is_none());
+    }
+
+    #[test]
+    fn find_enum_item_by_value() {
+        let info = find_enum_item("Material", 256u32).unwrap();
+        assert_eq!(info.value, 256);
+
+        assert!(find_enum_item("Material", u32::MAX).is_none());
+    }
+
+    #[test]
+    fn property_info_includes_tags_and_scriptability() {
+        let info = find_property_info("Part", "Anchored").unwrap();
+        assert_eq!(info.scriptability, Scriptability::ReadWrite);
+        assert!(info.tags.is_empty());
+
+        let info = find_property_info("BasePart", "FormFactor").unwrap();
+        assert!(info.tags.contains(&PropertyTag::Deprecated));
+    }
+
+    #[test]
+    fn collect_property_infos_valid() {
+        let infos = collect_property_infos("Part").unwrap();
+
+        // Should contain properties declared directly on Part...
+        assert!(infos.iter().any(|(name, _)| name == "Shape"));
+        // ...as well as properties inherited from its superclasses
+        assert!(infos.iter().any(|(name, _)| name == "Anchored"));
+        assert!(infos.iter().any(|(name, _)| name == "Name"));
+
+        // Properties should not be duplicated across the superclass chain
+        let shape_count = infos.iter().filter(|(name, _)| name == "Shape").count();
+        assert_eq!(shape_count, 1);
+    }
+
+    #[test]
+    fn collect_property_infos_invalid() {
+        assert!(collect_property_infos("NotAClass").is_none());
+    }
+
+    #[test]
+    fn default_properties_for_valid() {
+        let defaults = default_properties_for("Part").unwrap();
+
+        // Should contain a default declared directly on Part...
+        assert!(defaults.contains_key("Shape"));
+        // ...as well as defaults inherited from its superclasses
+        assert!(defaults.contains_key("Anchored"));
+    }
+
+    #[test]
+    fn default_properties_for_invalid() {
+        assert!(default_properties_for("NotAClass").is_none());
+    }
 }